@@ -43,7 +43,7 @@ fn run(num_threads: usize, iter: usize) {
                             test::black_box(*r);
                             break;
                         }
-                        Err(Blocked::LostRace) => {
+                        Err(Blocked::Readers) => {
                             spin_loop_hint();
                         }
                         Err(_) => unreachable!(),
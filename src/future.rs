@@ -2,10 +2,12 @@ use atomic_prim_traits::AtomicInt;
 use event_listener::{Event, EventListener};
 use primitive_traits::*;
 use simple_mutex::Mutex;
+use slab::Slab;
 use std::future::Future;
 use std::pin::Pin;
 use std::task::{Context, Poll, Waker};
 use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, spin_loop_hint};
 use super::{Blocked, RWLease};
 
@@ -17,7 +19,7 @@ pub struct AsyncRWLease<T, A=AtomicUsize>
 where A: AtomicInt, A::Prim: AddSign {
     pub(crate) lease: RWLease<T,A>,
     pub(crate) read: Event,
-    pub(crate) write: Mutex<Option<Waker>>, // *cry*
+    pub(crate) writers: Mutex<Slab<Option<Waker>>>,
 }
 
 impl<T, A> AsyncRWLease<T, A>
@@ -27,7 +29,7 @@ where A: AtomicInt, A::Prim: AddSign + Into<usize> {
         AsyncRWLease {
             lease: RWLease::new(value),
             read: Event::new(),
-            write: Mutex::new(None),
+            writers: Mutex::new(Slab::new()),
         }
     }
 
@@ -39,28 +41,120 @@ where A: AtomicInt, A::Prim: AddSign + Into<usize> {
         PollWriteGuard::new(self, wait_on_write)
     }
 
+    pub fn poll_upgradable_read<'a>(&'a self, wait_on_write: bool) -> PollUpgradableReadGuard<'a, T, A> {
+        PollUpgradableReadGuard::new(self, wait_on_write)
+    }
+
+    /// Like `poll_read`, but the guard owns a clone of the `Arc` instead
+    /// of borrowing it, so it can be held across a `'static` task.
+    pub fn read_arc(self: &Arc<Self>, wait_on_write: bool) -> PollOwnedReadGuard<T, A> {
+        PollOwnedReadGuard::new(self.clone(), wait_on_write)
+    }
+
+    /// Like `poll_write`, but the guard owns a clone of the `Arc` instead
+    /// of borrowing it.
+    pub fn write_arc(self: &Arc<Self>, wait_on_write: bool) -> PollOwnedWriteGuard<T, A> {
+        PollOwnedWriteGuard::new(self.clone(), wait_on_write)
+    }
+
     pub fn into_inner(self) -> T {
         self.lease.into_inner()
     }
 
-    fn done_reading(&self) {
-        let mask = <<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
-        let one = <A::Prim as Integer>::ONE;
-        let old = self.lease.done_reading();
-        if old == mask + one { // writing waiting, we're the last reader
-            let mut lock = self.write.lock();
-            if let Some(waker) = lock.take() {
-                waker.wake();
+    /// Block the current thread until a read lease is available. Uses
+    /// the same `read` `Event` the async path listens on, so a parked
+    /// thread here is woken by an async guard dropping and vice versa.
+    pub fn read_blocking(&self) -> AsyncReadGuard<T, A> {
+        loop {
+            if self.lease.poll_read().is_ok() {
+                return AsyncReadGuard::new(self);
+            }
+            // Register before re-checking, so we can't miss a wakeup
+            // that happens between the failed attempt above and here.
+            let listener = self.read.listen();
+            if self.lease.poll_read().is_ok() {
+                return AsyncReadGuard::new(self);
+            }
+            listener.wait();
+        }
+    }
+
+    /// Block the current thread until a write lease is available.
+    pub fn write_blocking(&self) -> AsyncWriteGuard<T, A> {
+        let drained = loop {
+            match self.lease.poll_write_mark() {
+                Ok(drained) => break drained,
+                Err(Blocked::Writer) => {
+                    let listener = self.read.listen();
+                    match self.lease.poll_write_mark() {
+                        Ok(drained) => break drained,
+                        Err(Blocked::Writer) => listener.wait(),
+                        Err(Blocked::Readers) => unreachable!(),
+                    }
+                }
+                Err(Blocked::Readers) => unreachable!(),
+            }
+        };
+        if !drained {
+            loop {
+                if self.lease.poll_write_upgrade() {
+                    break;
+                }
+                let listener = self.read.listen();
+                if self.lease.poll_write_upgrade() {
+                    break;
+                }
+                listener.wait();
+            }
+        }
+        AsyncWriteGuard::new(self)
+    }
+
+    /// Wake whoever needs waking after a reader (plain or upgradable)
+    /// released its share, given the state from just before release.
+    fn notify_after_release(&self, old: <A as AtomicInt>::Prim) {
+        let writer_bit = <A::Prim as Integer>::ONE;
+        let upgradable_bit = writer_bit + writer_bit;
+        let one_reader = upgradable_bit + upgradable_bit;
+        let reader_bits = !(writer_bit | upgradable_bit);
+        if old & writer_bit != <A::Prim as Integer>::ZERO && old & reader_bits == one_reader {
+            // we were the last reader, and a writer is marked and
+            // waiting to drain into the lease: wake them all up, they
+            // can race for it. A blocking writer parks on `read` rather
+            // than registering in the slab, so wake that too.
+            self.read.notify(<A::Prim as Integer>::MAX.into());
+            let mut writers = self.writers.lock();
+            for (_, waker) in writers.iter_mut() {
+                if let Some(waker) = waker.take() {
+                    waker.wake();
+                }
             }
-        } else if old < mask { // there may be a reader waiting
+        } else if old > <A::Prim as Integer>::MAX - one_reader {
+            // a reader may have just been turned away by the overflow guard
             self.read.notify_additional(1);
         }
     }
 
+    fn done_reading(&self) {
+        let old = self.lease.done_reading();
+        self.notify_after_release(old);
+    }
+
+    fn done_upgradable_reading(&self) {
+        let old = self.lease.done_upgradable_reading();
+        self.notify_after_release(old);
+    }
+
     fn done_writing(&self) {
-        let max_readers = !<<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
         self.lease.done_writing();
-        self.read.notify(max_readers.into());
+        self.read.notify(<A::Prim as Integer>::MAX.into());
+        // wake every writer queued up behind us too, not just readers
+        let mut writers = self.writers.lock();
+        for (_, waker) in writers.iter_mut() {
+            if let Some(waker) = waker.take() {
+                waker.wake();
+            }
+        }
     }
 
 }
@@ -118,6 +212,50 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     }
 }
 
+pub struct PollUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    pub(crate) lease: Option<&'a AsyncRWLease<T, A>>,
+    /// If it's write locked, we may want to fail because it could take a while.
+    pub(crate) wait_on_write: bool,
+    /// How we will get an event it's ready to read
+    pub(crate) listener: Option<EventListener>,
+}
+
+impl<'a, T, A> PollUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    fn new(lease: &'a AsyncRWLease<T,A>, wait_on_write: bool) -> Self {
+        PollUpgradableReadGuard { lease: Some(lease), wait_on_write, listener: None }
+    }
+}
+
+impl<'a, T, A> Future for PollUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    type Output = Result<AsyncUpgradableReadGuard<'a, T, A>, Blocked>;
+    fn poll(self: Pin<&mut Self>, _ctx: &mut Context) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(lease) = this.lease {
+            let mut last_failure: Option<Blocked> = None;
+            for _ in 1..READ_SPINS {
+                if let Err(e) = lease.lease.poll_upgradable_read() {
+                    last_failure = Some(e);
+                    spin_loop_hint();
+                } else {
+                    let guard = Ok(AsyncUpgradableReadGuard::new(this.lease.take().unwrap()));
+                    return Poll::Ready(guard);
+                }
+            }
+            if (Some(Blocked::Writer) == last_failure) && !this.wait_on_write {
+                Poll::Ready(Err(Blocked::Writer))
+            } else {
+                this.listener = Some(lease.read.listen());
+                Poll::Pending
+            }
+        } else {
+            panic!("PollUpgradableReadGuard already resolved!")
+        }
+    }
+}
+
 pub struct PollWriteGuard<'a, T, A>
 where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     pub(crate) lease: Option<&'a AsyncRWLease<T, A>>,
@@ -125,12 +263,33 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     pub(crate) wait_on_write: bool,
     /// Did we set the mark?
     marked: bool,
+    /// Our slot in `writers`, once we've registered a waker there.
+    key: Option<usize>,
 }
 
 impl<'a, T, A> PollWriteGuard<'a, T, A>
 where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     fn new(lease: &'a AsyncRWLease<T,A>, wait_on_write: bool) -> Self {
-        PollWriteGuard { lease: Some(lease), wait_on_write, marked: false }
+        PollWriteGuard { lease: Some(lease), wait_on_write, marked: false, key: None }
+    }
+
+    /// Register (or re-register, on a later poll) our waker in the
+    /// lease's writer slab, so it survives alongside every other
+    /// blocked writer instead of clobbering them.
+    fn register(&mut self, lease: &AsyncRWLease<T, A>, ctx: &Context) {
+        let waker = ctx.waker().clone();
+        let mut writers = lease.writers.lock();
+        match self.key {
+            Some(key) => writers[key] = Some(waker),
+            None => self.key = Some(writers.insert(Some(waker))),
+        }
+    }
+
+    /// Drop our slot in the writer slab, if we ever took one.
+    fn forget(&mut self, lease: &AsyncRWLease<T, A>) {
+        if let Some(key) = self.key.take() {
+            lease.writers.lock().remove(key);
+        }
     }
 }
 
@@ -138,6 +297,7 @@ impl<'a, T, A> Drop for PollWriteGuard<'a, T, A>
 where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     fn drop(&mut self) {
         if let Some(lease) = self.lease.take() {
+            self.forget(lease);
             lease.done_writing();
         }
     }
@@ -153,14 +313,16 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
                 match lease.lease.poll_write_mark() {
                     Ok(false) => { this.marked = true; } // fall through
                     Ok(true) => {
+                        this.forget(lease);
                         return Poll::Ready(Ok(AsyncWriteGuard::new(this.lease.take().unwrap())));
                     }
                     Err(err) => { // only blocks on other writers
                         if this.wait_on_write {
-                            *lease.write.lock() = Some(ctx.waker().clone());
+                            this.register(lease, ctx);
                             match lease.lease.poll_write_mark() { // race - maybe it just finished?
                                 Ok(false) => { this.marked = true; }
                                 Ok(true) => {
+                                    this.forget(lease);
                                     let lease = this.lease.take().unwrap();
                                     return Poll::Ready(Ok(AsyncWriteGuard::new(lease)));
                                 }
@@ -174,14 +336,15 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
             }
             for _ in 1..WRITE_SPINS {
                 if lease.lease.poll_write_upgrade() {
+                    this.forget(lease);
                     return Poll::Ready(Ok(AsyncWriteGuard::new(this.lease.take().unwrap())));
                 } else {
                     spin_loop_hint();
                 }
             }
-            *lease.write.lock() = Some(ctx.waker().clone());
+            this.register(lease, ctx);
         }
-        Poll::Pending // Either we already completed 
+        Poll::Pending // Either we already completed
     }
 }
 
@@ -196,6 +359,31 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     fn new(lease: &'a AsyncRWLease<T, A>) -> Self {
         AsyncReadGuard { lease }
     }
+
+    /// Project to a field of `T`, releasing the read lease on drop
+    /// exactly as this guard would have.
+    pub fn map<U, F>(self, f: F) -> AsyncMappedReadGuard<'a, T, A, U>
+    where F: FnOnce(&T) -> &U {
+        let lease = self.lease;
+        let value = f(unsafe { &*lease.lease.value.get() }) as *const U;
+        core::mem::forget(self);
+        AsyncMappedReadGuard { lease, value }
+    }
+
+    /// Like `map`, but the closure can decline the projection, handing
+    /// the original guard back.
+    pub fn try_map<U, F>(self, f: F) -> Result<AsyncMappedReadGuard<'a, T, A, U>, Self>
+    where F: FnOnce(&T) -> Option<&U> {
+        match f(unsafe { &*self.lease.lease.value.get() }) {
+            Some(value) => {
+                let value = value as *const U;
+                let lease = self.lease;
+                core::mem::forget(self);
+                Ok(AsyncMappedReadGuard { lease, value })
+            }
+            None => Err(self),
+        }
+    }
 }
 
 impl<'a, T, A> Deref for AsyncReadGuard<'a, T, A>
@@ -213,6 +401,123 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     }
 }
 
+/// Requires the `event-listener` feature. Reads like an `AsyncReadGuard`,
+/// but can later upgrade to an `AsyncWriteGuard` without the lease ever
+/// touching zero readers. At most one of these may be outstanding at a time.
+pub struct AsyncUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    pub(crate) lease: &'a AsyncRWLease<T, A>,
+}
+
+impl<'a, T, A> AsyncUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    fn new(lease: &'a AsyncRWLease<T, A>) -> Self {
+        AsyncUpgradableReadGuard { lease }
+    }
+
+    /// Upgrade to exclusive write access without ever releasing shared
+    /// access in between.
+    pub fn upgrade(self) -> PollUpgradeGuard<'a, T, A> {
+        PollUpgradeGuard::new(self)
+    }
+}
+
+impl<'a, T, A> Deref for AsyncUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.lease.value.get() }
+    }
+}
+
+impl<'a, T, A> Drop for AsyncUpgradableReadGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    fn drop(&mut self) {
+        self.lease.done_upgradable_reading();
+    }
+}
+
+pub struct PollUpgradeGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    guard: Option<AsyncUpgradableReadGuard<'a, T, A>>,
+    /// Have we already given up our reader share and marked ourselves as writer?
+    marked: bool,
+    /// Our slot in `writers`, once we've registered a waker there.
+    key: Option<usize>,
+}
+
+impl<'a, T, A> PollUpgradeGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    fn new(guard: AsyncUpgradableReadGuard<'a, T, A>) -> Self {
+        PollUpgradeGuard { guard: Some(guard), marked: false, key: None }
+    }
+
+    fn register(&mut self, lease: &AsyncRWLease<T, A>, ctx: &Context) {
+        let waker = ctx.waker().clone();
+        let mut writers = lease.writers.lock();
+        match self.key {
+            Some(key) => writers[key] = Some(waker),
+            None => self.key = Some(writers.insert(Some(waker))),
+        }
+    }
+
+    fn forget(&mut self, lease: &AsyncRWLease<T, A>) {
+        if let Some(key) = self.key.take() {
+            lease.writers.lock().remove(key);
+        }
+    }
+}
+
+impl<'a, T, A> Future for PollUpgradeGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    type Output = AsyncWriteGuard<'a, T, A>;
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        let lease = match &this.guard {
+            Some(guard) => guard.lease,
+            None => panic!("PollUpgradeGuard already resolved!"),
+        };
+        if !this.marked {
+            match lease.lease.try_mark_upgrade() {
+                Ok(()) => { this.marked = true; }
+                Err(_) => {
+                    // some other writer already claimed the bit; wait
+                    // for it to finish and try again
+                    this.register(lease, ctx);
+                    return Poll::Pending;
+                }
+            }
+        }
+        for _ in 1..WRITE_SPINS {
+            if lease.lease.poll_write_upgrade() {
+                this.forget(lease);
+                // we've become the writer: skip the guard's own drop,
+                // which would release the upgradable bit we're keeping.
+                core::mem::forget(this.guard.take().unwrap());
+                return Poll::Ready(AsyncWriteGuard::new(lease));
+            } else {
+                spin_loop_hint();
+            }
+        }
+        this.register(lease, ctx);
+        Poll::Pending
+    }
+}
+
+impl<'a, T, A> Drop for PollUpgradeGuard<'a, T, A>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
+    fn drop(&mut self) {
+        if let Some(guard) = self.guard.take() {
+            self.forget(guard.lease);
+            if self.marked {
+                guard.lease.lease.unmark_upgrade();
+            }
+            // guard's own drop now runs, releasing the upgradable bit
+            // and our reader share as normal.
+        }
+    }
+}
+
 /// Requires the `event-listener` feature.
 pub struct AsyncWriteGuard<'a, T, A>
 where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
@@ -224,6 +529,45 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
     fn new(lease: &'a AsyncRWLease<T, A>) -> Self {
         AsyncWriteGuard { lease }
     }
+
+    /// Give up exclusive access, becoming an upgradable reader instead
+    /// of releasing the lease entirely. Safe even if this guard didn't
+    /// come from an upgrade: while we hold the writer bit, no other
+    /// upgradable reader can exist to clash with.
+    pub fn downgrade(self) -> AsyncUpgradableReadGuard<'a, T, A> {
+        let lease = self.lease;
+        // skip the drop handler, we're recycling our claim rather than
+        // releasing it
+        core::mem::forget(self);
+        lease.lease.downgrade_from_write();
+        lease.read.notify(<A::Prim as Integer>::MAX.into());
+        AsyncUpgradableReadGuard::new(lease)
+    }
+
+    /// Project to a field of `T`, releasing the write lease on drop
+    /// exactly as this guard would have.
+    pub fn map<U, F>(self, f: F) -> AsyncMappedWriteGuard<'a, T, A, U>
+    where F: FnOnce(&mut T) -> &mut U {
+        let lease = self.lease;
+        let value = f(unsafe { &mut *lease.lease.value.get() }) as *mut U;
+        core::mem::forget(self);
+        AsyncMappedWriteGuard { lease, value }
+    }
+
+    /// Like `map`, but the closure can decline the projection, handing
+    /// the original guard back.
+    pub fn try_map<U, F>(self, f: F) -> Result<AsyncMappedWriteGuard<'a, T, A, U>, Self>
+    where F: FnOnce(&mut T) -> Option<&mut U> {
+        match f(unsafe { &mut *self.lease.lease.value.get() }) {
+            Some(value) => {
+                let value = value as *mut U;
+                let lease = self.lease;
+                core::mem::forget(self);
+                Ok(AsyncMappedWriteGuard { lease, value })
+            }
+            None => Err(self),
+        }
+    }
 }
 
 impl<'a, T, A> Deref for AsyncWriteGuard<'a, T, A>
@@ -247,3 +591,333 @@ where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a {
         self.lease.done_writing();
     }
 }
+
+pub struct PollOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    pub(crate) lease: Option<Arc<AsyncRWLease<T, A>>>,
+    /// If it's write locked, we may want to fail because it could take a while.
+    pub(crate) wait_on_write: bool,
+    /// How we will get an event it's ready to read
+    pub(crate) listener: Option<EventListener>,
+}
+
+impl<T, A> PollOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn new(lease: Arc<AsyncRWLease<T, A>>, wait_on_write: bool) -> Self {
+        PollOwnedReadGuard { lease: Some(lease), wait_on_write, listener: None }
+    }
+}
+
+impl<T, A> Future for PollOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    type Output = Result<AsyncOwnedReadGuard<T, A>, Blocked>;
+    fn poll(self: Pin<&mut Self>, _ctx: &mut Context) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(lease) = this.lease.clone() {
+            let mut last_failure: Option<Blocked> = None;
+            for _ in 1..READ_SPINS {
+                if let Err(e) = lease.lease.poll_read() {
+                    last_failure = Some(e);
+                    spin_loop_hint();
+                } else {
+                    let guard = Ok(AsyncOwnedReadGuard::new(this.lease.take().unwrap()));
+                    return Poll::Ready(guard);
+                }
+            }
+            if (Some(Blocked::Writer) == last_failure) && !this.wait_on_write {
+                Poll::Ready(Err(Blocked::Writer))
+            } else {
+                this.listener = Some(lease.read.listen());
+                Poll::Pending
+            }
+        } else {
+            panic!("PollOwnedReadGuard already resolved!")
+        }
+    }
+}
+
+impl<T, A> Drop for PollOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn drop(&mut self) {
+        if let Some(lease) = self.lease.take() {
+            lease.done_writing();
+        }
+    }
+}
+
+/// Requires the `event-listener` feature. Like `AsyncReadGuard`, but owns
+/// a clone of the `Arc` instead of borrowing the lease, so it can be held
+/// across a `'static` task.
+pub struct AsyncOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    pub(crate) lease: Arc<AsyncRWLease<T, A>>,
+}
+
+impl<T, A> AsyncOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn new(lease: Arc<AsyncRWLease<T, A>>) -> Self {
+        AsyncOwnedReadGuard { lease }
+    }
+}
+
+impl<T, A> Deref for AsyncOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.lease.value.get() }
+    }
+}
+
+impl<T, A> Drop for AsyncOwnedReadGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn drop(&mut self) {
+        self.lease.done_reading();
+    }
+}
+
+pub struct PollOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    pub(crate) lease: Option<Arc<AsyncRWLease<T, A>>>,
+    /// If it's write locked, we may want to fail because it could take a while.
+    pub(crate) wait_on_write: bool,
+    /// Did we set the mark?
+    marked: bool,
+    /// Our slot in `writers`, once we've registered a waker there.
+    key: Option<usize>,
+}
+
+impl<T, A> PollOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn new(lease: Arc<AsyncRWLease<T, A>>, wait_on_write: bool) -> Self {
+        PollOwnedWriteGuard { lease: Some(lease), wait_on_write, marked: false, key: None }
+    }
+
+    /// Register (or re-register, on a later poll) our waker in the
+    /// lease's writer slab, so it survives alongside every other
+    /// blocked writer instead of clobbering them.
+    fn register(&mut self, lease: &AsyncRWLease<T, A>, ctx: &Context) {
+        let waker = ctx.waker().clone();
+        let mut writers = lease.writers.lock();
+        match self.key {
+            Some(key) => writers[key] = Some(waker),
+            None => self.key = Some(writers.insert(Some(waker))),
+        }
+    }
+
+    /// Drop our slot in the writer slab, if we ever took one.
+    fn forget(&mut self, lease: &AsyncRWLease<T, A>) {
+        if let Some(key) = self.key.take() {
+            lease.writers.lock().remove(key);
+        }
+    }
+}
+
+impl<T, A> Drop for PollOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn drop(&mut self) {
+        if let Some(lease) = self.lease.take() {
+            self.forget(&lease);
+            lease.done_writing();
+        }
+    }
+}
+
+impl<T, A> Future for PollOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    type Output = Result<AsyncOwnedWriteGuard<T, A>, Blocked>;
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context) -> Poll<Self::Output> {
+        let this = unsafe { self.get_unchecked_mut() };
+        if let Some(lease) = this.lease.clone() {
+            if !this.marked {
+                match lease.lease.poll_write_mark() {
+                    Ok(false) => { this.marked = true; } // fall through
+                    Ok(true) => {
+                        this.forget(&lease);
+                        return Poll::Ready(Ok(AsyncOwnedWriteGuard::new(this.lease.take().unwrap())));
+                    }
+                    Err(err) => { // only blocks on other writers
+                        if this.wait_on_write {
+                            this.register(&lease, ctx);
+                            match lease.lease.poll_write_mark() { // race - maybe it just finished?
+                                Ok(false) => { this.marked = true; }
+                                Ok(true) => {
+                                    this.forget(&lease);
+                                    let lease = this.lease.take().unwrap();
+                                    return Poll::Ready(Ok(AsyncOwnedWriteGuard::new(lease)));
+                                }
+                                _ => { return Poll::Pending; }
+                            }
+                        } else {
+                            return Poll::Ready(Err(err))
+                        }
+                    }
+                }
+            }
+            for _ in 1..WRITE_SPINS {
+                if lease.lease.poll_write_upgrade() {
+                    this.forget(&lease);
+                    return Poll::Ready(Ok(AsyncOwnedWriteGuard::new(this.lease.take().unwrap())));
+                } else {
+                    spin_loop_hint();
+                }
+            }
+            this.register(&lease, ctx);
+        }
+        Poll::Pending // Either we already completed
+    }
+}
+
+/// Requires the `event-listener` feature. Like `AsyncWriteGuard`, but owns
+/// a clone of the `Arc` instead of borrowing the lease, so it can be held
+/// across a `'static` task.
+pub struct AsyncOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    pub(crate) lease: Arc<AsyncRWLease<T, A>>,
+}
+
+impl<T, A> AsyncOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn new(lease: Arc<AsyncRWLease<T, A>>) -> Self {
+        AsyncOwnedWriteGuard { lease }
+    }
+}
+
+impl<T, A> Deref for AsyncOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.lease.value.get() }
+    }
+}
+
+impl<T, A> DerefMut for AsyncOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lease.lease.value.get() }
+    }
+}
+
+impl<T, A> Drop for AsyncOwnedWriteGuard<T, A>
+where A: AtomicInt, A::Prim: AddSign + Into<usize> {
+    fn drop(&mut self) {
+        self.lease.done_writing();
+    }
+}
+
+/// A read guard projected onto one of `T`'s fields by
+/// `AsyncReadGuard::map` or `try_map`. Releases the read lease on drop,
+/// same as the `AsyncReadGuard` it came from.
+#[derive(Debug)]
+pub struct AsyncMappedReadGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    lease: &'a AsyncRWLease<T, A>,
+    value: *const U,
+}
+
+impl<'a, T, A, U> Deref for AsyncMappedReadGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T, A, U> Drop for AsyncMappedReadGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    fn drop(&mut self) {
+        self.lease.done_reading();
+    }
+}
+
+/// A write guard projected onto one of `T`'s fields by
+/// `AsyncWriteGuard::map` or `try_map`. Releases the write lease on
+/// drop, same as the `AsyncWriteGuard` it came from.
+#[derive(Debug)]
+pub struct AsyncMappedWriteGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    lease: &'a AsyncRWLease<T, A>,
+    value: *mut U,
+}
+
+impl<'a, T, A, U> Deref for AsyncMappedWriteGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T, A, U> DerefMut for AsyncMappedWriteGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl<'a, T, A, U> Drop for AsyncMappedWriteGuard<'a, T, A, U>
+where A: 'a + AtomicInt, A::Prim: AddSign + Into<usize>, T: 'a, U: 'a {
+    fn drop(&mut self) {
+        self.lease.done_writing();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::{RawWaker, RawWakerVTable};
+
+    fn noop_waker() -> Waker {
+        fn clone(_: *const ()) -> RawWaker { raw() }
+        fn noop(_: *const ()) {}
+        fn raw() -> RawWaker {
+            RawWaker::new(core::ptr::null(), &RawWakerVTable::new(clone, noop, noop, noop))
+        }
+        unsafe { Waker::from_raw(raw()) }
+    }
+
+    #[test]
+    fn two_pending_writers_each_keep_their_own_slot() {
+        let lease = AsyncRWLease::<usize>::new(123);
+        // Hold a reader open so neither writer below can complete, and
+        // both end up parked in the writer slab.
+        let _r = lease.lease.read().expect("read guard");
+
+        let waker = noop_waker();
+        let mut ctx = Context::from_waker(&waker);
+
+        let mut first = lease.poll_write(true);
+        let mut second = lease.poll_write(true);
+        let first = unsafe { Pin::new_unchecked(&mut first) };
+        let second = unsafe { Pin::new_unchecked(&mut second) };
+
+        assert!(first.poll(&mut ctx).is_pending());
+        assert!(second.poll(&mut ctx).is_pending());
+
+        // A single-slot waker would have had the second writer clobber
+        // the first's registration; the slab keeps both alive.
+        assert_eq!(lease.writers.lock().len(), 2);
+    }
+
+    #[test]
+    fn write_blocking_wakes_when_last_reader_drains() {
+        let lease = Arc::new(AsyncRWLease::<usize>::new(123));
+        // Must be an async-aware reader: the plain `RWLease::read` guard's
+        // drop only does a bare `fetch_sub`, with no `notify_after_release`
+        // to wake the writer parked on `read` below.
+        let r = lease.read_blocking();
+
+        let writer = lease.clone();
+        let handle = std::thread::spawn(move || {
+            let mut w = writer.write_blocking();
+            *w = 124;
+        });
+
+        // Give the spawned thread a chance to mark the writer and park
+        // on `read` before we drop the last reader.
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        drop(r);
+
+        handle.join().expect("writer thread panicked");
+        assert_eq!(*lease.lease.read().expect("read guard"), 124);
+    }
+}
@@ -1,5 +1,10 @@
-#![no_std]
+// `future` pulls in `std` for its executor integration, so only opt out
+// of `no_std` when the `async` feature (and therefore `future`) is on.
+#![cfg_attr(not(feature = "async"), no_std)]
 
+extern crate alloc;
+
+use alloc::sync::Arc;
 use core::cell::UnsafeCell;
 use core::ops::{Deref, DerefMut};
 use core::sync::atomic::{AtomicUsize, Ordering};
@@ -7,13 +12,11 @@ use core::sync::atomic::{AtomicUsize, Ordering};
 use atomic_prim_traits::AtomicInt;
 use primitive_traits::*;
 
-// These are currently broken. We'll just not incude them for now so
-// we can get the 0.1.0 release out
-//
-// #[cfg(feature="async")]
-// mod future;
-// #[cfg(feature="async")]
-// pub use future::*;
+/// Async guards (`AsyncRWLease` and friends) built on `event-listener`.
+#[cfg(feature = "async")]
+mod future;
+#[cfg(feature = "async")]
+pub use future::*;
 
 /// Can happen when we try to take a lease.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -22,14 +25,13 @@ pub enum Blocked {
     Readers,
     /// There is a writer. Maybe it won't be just a moment, who knows?
     Writer,
-    /// We were beaten by another thread in the CAS
-    LostRace,
 }
 
 /// An RWLock, but:
 /// * Choose your atomic unsigned integer for storage:
-///   * We will steal the high bit for the writer.
-///   * We will count readers on the remaining bits.
+///   * We will steal the low bit for the writer.
+///   * We will steal the next bit up for an upgradable reader.
+///   * We will count readers on the remaining bits, four at a time.
 /// * Bring your own synchronisation primitive:
 ///   * No looping
 /// * Writers wait for a lack of readers before assuming Write access.
@@ -59,7 +61,7 @@ where
         }
     }
 
-    /// Attempt to take a read lease by CAS or explain why we couldn't.
+    /// Attempt to take a read lease or explain why we couldn't.
     pub fn read(&self) -> Result<ReadGuard<T, A>, Blocked> {
         self.poll_read()?;
         Ok(ReadGuard::new(&self))
@@ -70,64 +72,184 @@ where
             .map(|ready| DrainGuard::new(&self, ready))
     }
 
+    /// Take a read lease that can later be upgraded to a write lease
+    /// without ever releasing shared access in between. Only one
+    /// upgradable reader may be outstanding at a time.
+    pub fn upgradable_read(&self) -> Result<UpgradableReadGuard<T, A>, Blocked> {
+        self.poll_upgradable_read()?;
+        Ok(UpgradableReadGuard::new(&self))
+    }
+
+    /// Like `read`, but the guard owns a clone of the `Arc` instead of
+    /// borrowing it, so it can outlive the scope that took it out (e.g.
+    /// to move into a spawned thread).
+    pub fn read_owned(self: &Arc<Self>) -> Result<OwnedReadGuard<T, A>, Blocked> {
+        self.poll_read()?;
+        Ok(OwnedReadGuard::new(self.clone()))
+    }
+
+    /// Like `write`, but the guard owns a clone of the `Arc` instead of
+    /// borrowing it.
+    pub fn write_owned(self: &Arc<Self>) -> Result<OwnedDrainGuard<T, A>, Blocked> {
+        self.poll_write_mark()
+            .map(|ready| OwnedDrainGuard::new(self.clone(), ready))
+    }
+
     pub fn into_inner(self) -> T {
         self.value.into_inner()
     }
 
+    /// Lock-free: a reader only retries if the atomic changed underneath
+    /// it (another reader claiming a slot, or a writer claiming the
+    /// lease); it never spins waiting for a writer to finish.
+    ///
+    /// This is deliberately not a wait-free single `fetch_add`. That
+    /// design adds unconditionally and only checks for overflow
+    /// afterwards, which for a storage type
+    /// like `AtomicU8` — where reader counting fills every bit above
+    /// the writer/upgradable ones, with no spare headroom — means a
+    /// reader arriving at capacity wraps the *whole* word back to zero.
+    /// A writer can observe that transiently-zero word as "nothing
+    /// outstanding" and take exclusive access while real readers are
+    /// still live. There's no way to publish the add and repair it
+    /// after the fact without that window existing, so small-`A` types
+    /// (which this crate explicitly supports) need the check to happen
+    /// before the atomic is touched, hence the CAS below.
     fn poll_read(&self) -> Result<(), Blocked> {
-        let mask = <<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
-        let current = self.atomic.load(Ordering::SeqCst);
-        if current < <A::Prim as Integer>::MAX {
-            // avoid overflow on the next line
-            let new = current + <A::Prim as Integer>::ONE;
-            if new < mask {
-                // Hot path, if we assume writes and read saturation are
-                // rare. I would like to remove the CAS from here, but
-                // until we have saturating addition or more complex
-                // atomic ops, that doesn't seem possible.
-                self.atomic
-                    .compare_exchange_weak(current, new, Ordering::SeqCst, Ordering::SeqCst)
-                    .map(drop)
-                    .map_err(|_| Blocked::LostRace)
-            } else if (current & mask) != mask {
-                Err(Blocked::Readers)
-            } else {
-                Err(Blocked::Writer)
+        let writer_bit = Self::writer_bit();
+        let one_reader = Self::one_reader();
+        let mut old = self.atomic.load(Ordering::Acquire);
+        loop {
+            if old & writer_bit != <A::Prim as Integer>::ZERO {
+                // A writer holds (or is draining into) the lease.
+                return Err(Blocked::Writer);
+            }
+            if old > <A::Prim as Integer>::MAX - one_reader {
+                // Adding our share would wrap the reader count into the
+                // writer/upgradable bits. Reject before ever attempting
+                // the add, so that bogus state is never published, even
+                // transiently, for a concurrent writer to act on.
+                return Err(Blocked::Readers);
+            }
+            match self.atomic.compare_exchange(
+                old,
+                old + one_reader,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(()),
+                Err(seen) => old = seen,
             }
-        } else {
-            Err(Blocked::Writer)
         }
     }
 
     fn poll_write_mark(&self) -> Result<bool, Blocked> {
-        let mask = <<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
-        let ret = self.atomic.fetch_or(mask, Ordering::SeqCst);
+        let writer_bit = Self::writer_bit();
+        let ret = self.atomic.fetch_or(writer_bit, Ordering::SeqCst);
 
         if ret == <A::Prim as Integer>::ZERO {
             Ok(true)
-        } else if (ret & mask) != mask {
-            // No readers
+        } else if ret & writer_bit == <A::Prim as Integer>::ZERO {
+            // No writer yet, but readers to drain
             Ok(false)
         } else {
-            // We'll have to wait for some readers
+            // We'll have to wait for some writer
             Err(Blocked::Writer)
         }
     }
 
+    /// True once every reader-count bit has drained away. The writer
+    /// and upgradable bits are ignored: an upgrading reader keeps its
+    /// upgradable bit set right through the transition.
     fn poll_write_upgrade(&self) -> bool {
-        let drained = <<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
-        drained == self.atomic.load(Ordering::SeqCst)
+        let reader_bits = !(Self::writer_bit() | Self::upgradable_bit());
+        self.atomic.load(Ordering::SeqCst) & reader_bits == <A::Prim as Integer>::ZERO
+    }
+
+    fn poll_upgradable_read(&self) -> Result<(), Blocked> {
+        let writer_bit = Self::writer_bit();
+        let upgradable_bit = Self::upgradable_bit();
+        let old = self.atomic.fetch_or(upgradable_bit, Ordering::AcqRel);
+        if old & (writer_bit | upgradable_bit) != <A::Prim as Integer>::ZERO {
+            if old & upgradable_bit == <A::Prim as Integer>::ZERO {
+                // We raced a writer and never really held the bit;
+                // give it back.
+                self.atomic.fetch_and(!upgradable_bit, Ordering::AcqRel);
+            }
+            return Err(Blocked::Writer);
+        }
+        // We hold the upgradable slot; also take a normal reader's share.
+        if let Err(e) = self.poll_read() {
+            self.atomic.fetch_and(!upgradable_bit, Ordering::AcqRel);
+            return Err(e);
+        }
+        Ok(())
     }
 
     fn done_reading(&self) -> <A as AtomicInt>::Prim {
-        let one = <<A as AtomicInt>::Prim as Integer>::ONE;
-        self.atomic.fetch_sub(one, Ordering::SeqCst)
+        self.atomic.fetch_sub(Self::one_reader(), Ordering::SeqCst)
+    }
+
+    fn done_upgradable_reading(&self) -> <A as AtomicInt>::Prim {
+        self.atomic.fetch_and(!Self::upgradable_bit(), Ordering::SeqCst);
+        self.done_reading()
     }
 
     fn done_writing(&self) {
-        let mask = !<<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
+        // By the time a write completes, reader bits are all zero, so
+        // any upgradable bit still set can only be our own (kept alive
+        // through an upgrade transition): safe to drop here too.
+        let mask = !(Self::writer_bit() | Self::upgradable_bit());
         self.atomic.fetch_and(mask, Ordering::SeqCst);
     }
+
+    /// Claim the writer bit for an upgrading reader, failing if some
+    /// other writer already got there first. On success, also give up
+    /// our own reader share (without letting go of the upgradable bit);
+    /// undo with `unmark_upgrade` if other readers turn out to still be
+    /// around.
+    fn try_mark_upgrade(&self) -> Result<(), Blocked> {
+        let writer_bit = Self::writer_bit();
+        let old = self.atomic.fetch_or(writer_bit, Ordering::SeqCst);
+        if old & writer_bit != <A::Prim as Integer>::ZERO {
+            Err(Blocked::Writer)
+        } else {
+            self.atomic.fetch_sub(Self::one_reader(), Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn unmark_upgrade(&self) {
+        self.atomic.fetch_and(!Self::writer_bit(), Ordering::SeqCst);
+        self.atomic.fetch_add(Self::one_reader(), Ordering::SeqCst);
+    }
+
+    /// Shared bit-twiddling behind downgrading a write guard: claim the
+    /// upgradable bit and a reader's share, then give up the writer bit.
+    /// Safe unconditionally, since no other upgradable reader can exist
+    /// while we hold exclusive access.
+    fn downgrade_from_write(&self) {
+        self.atomic.fetch_or(Self::upgradable_bit(), Ordering::SeqCst);
+        self.atomic.fetch_add(Self::one_reader(), Ordering::SeqCst);
+        self.atomic.fetch_and(!Self::writer_bit(), Ordering::SeqCst);
+    }
+
+    /// The bit a writer sets to mark its claim on the lease.
+    fn writer_bit() -> <A as AtomicInt>::Prim {
+        <A::Prim as Integer>::ONE
+    }
+
+    /// The bit an upgradable reader sets. At most one may be set at once.
+    fn upgradable_bit() -> <A as AtomicInt>::Prim {
+        Self::writer_bit() + Self::writer_bit()
+    }
+
+    /// What a reader adds to take a share of the lease. Readers are
+    /// counted four at a time so they never collide with the writer or
+    /// upgradable bits.
+    fn one_reader() -> <A as AtomicInt>::Prim {
+        Self::upgradable_bit() + Self::upgradable_bit()
+    }
 }
 
 unsafe impl<T: Send> Send for RWLease<T> {}
@@ -177,8 +299,11 @@ where
     T: 'a,
 {
     fn drop(&mut self) {
-        let mask = !<<A::Prim as AddSign>::Signed as Integer>::MIN.drop_sign();
-        self.lease.atomic.fetch_and(mask, Ordering::SeqCst);
+        // Give up the writer bit we marked in `poll_write_mark`. Unlike
+        // `done_writing`, we can't also clear the upgradable bit here:
+        // readers (including an upgradable one) may still legitimately
+        // be draining, and it isn't ours to take away from them.
+        self.lease.atomic.fetch_and(!RWLease::<T, A>::writer_bit(), Ordering::SeqCst);
     }
 }
 
@@ -202,6 +327,36 @@ where
     fn new(lease: &'a RWLease<T, A>) -> ReadGuard<'a, T, A> {
         ReadGuard { lease }
     }
+
+    /// Project to a field of `T`, releasing the read lease on drop
+    /// exactly as this guard would have.
+    pub fn map<U, F>(self, f: F) -> MappedReadGuard<'a, T, A, U>
+    where
+        F: FnOnce(&T) -> &U,
+    {
+        let lease = self.lease;
+        let value = f(unsafe { &*lease.value.get() }) as *const U;
+        // skip the drop handler, the mapped guard takes over the release
+        core::mem::forget(self);
+        MappedReadGuard { lease, value }
+    }
+
+    /// Like `map`, but the closure can decline the projection, handing
+    /// the original guard back.
+    pub fn try_map<U, F>(self, f: F) -> Result<MappedReadGuard<'a, T, A, U>, Self>
+    where
+        F: FnOnce(&T) -> Option<&U>,
+    {
+        match f(unsafe { &*self.lease.value.get() }) {
+            Some(value) => {
+                let value = value as *const U;
+                let lease = self.lease;
+                core::mem::forget(self);
+                Ok(MappedReadGuard { lease, value })
+            }
+            None => Err(self),
+        }
+    }
 }
 
 impl<'a, T, A> Deref for ReadGuard<'a, T, A>
@@ -247,6 +402,49 @@ where
     fn new(lease: &'a RWLease<T, A>) -> WriteGuard<'a, T, A> {
         WriteGuard { lease }
     }
+
+    /// Give up exclusive access, becoming an upgradable reader instead
+    /// of releasing the lease entirely. Safe even if this guard didn't
+    /// come from an upgrade: while we hold the writer bit, no other
+    /// upgradable reader can exist to clash with.
+    pub fn downgrade(self) -> UpgradableReadGuard<'a, T, A> {
+        let lease = self.lease;
+        // skip the drop handler, we're recycling our claim rather than
+        // releasing it
+        core::mem::forget(self);
+        lease.downgrade_from_write();
+        UpgradableReadGuard::new(lease)
+    }
+
+    /// Project to a field of `T`, releasing the write lease on drop
+    /// exactly as this guard would have.
+    pub fn map<U, F>(self, f: F) -> MappedWriteGuard<'a, T, A, U>
+    where
+        F: FnOnce(&mut T) -> &mut U,
+    {
+        let lease = self.lease;
+        let value = f(unsafe { &mut *lease.value.get() }) as *mut U;
+        // skip the drop handler, the mapped guard takes over the release
+        core::mem::forget(self);
+        MappedWriteGuard { lease, value }
+    }
+
+    /// Like `map`, but the closure can decline the projection, handing
+    /// the original guard back.
+    pub fn try_map<U, F>(self, f: F) -> Result<MappedWriteGuard<'a, T, A, U>, Self>
+    where
+        F: FnOnce(&mut T) -> Option<&mut U>,
+    {
+        match f(unsafe { &mut *self.lease.value.get() }) {
+            Some(value) => {
+                let value = value as *mut U;
+                let lease = self.lease;
+                core::mem::forget(self);
+                Ok(MappedWriteGuard { lease, value })
+            }
+            None => Err(self),
+        }
+    }
 }
 
 impl<'a, T, A> Deref for WriteGuard<'a, T, A>
@@ -283,6 +481,307 @@ where
     }
 }
 
+/// This guard signifies shared, upgradable read access. It reads like a
+/// `ReadGuard`, but can later upgrade to a `WriteGuard` without the
+/// lease ever touching zero readers. At most one of these may be
+/// outstanding at a time.
+#[derive(Debug)]
+pub struct UpgradableReadGuard<'a, T, A>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+{
+    lease: &'a RWLease<T, A>,
+}
+
+impl<'a, T, A> UpgradableReadGuard<'a, T, A>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+{
+    fn new(lease: &'a RWLease<T, A>) -> UpgradableReadGuard<'a, T, A> {
+        UpgradableReadGuard { lease }
+    }
+
+    /// Attempts to upgrade to a WriteGuard without ever releasing shared
+    /// access in between. If a writer already holds the lease, or other
+    /// readers are still around, returns self so you can try again.
+    pub fn upgrade(self) -> Result<WriteGuard<'a, T, A>, Self> {
+        let lease = self.lease;
+        match lease.try_mark_upgrade() {
+            Err(_) => Err(self),
+            Ok(()) => {
+                if lease.poll_write_upgrade() {
+                    core::mem::forget(self);
+                    Ok(WriteGuard::new(lease))
+                } else {
+                    lease.unmark_upgrade();
+                    Err(self)
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T, A> Deref for UpgradableReadGuard<'a, T, A>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.value.get() }
+    }
+}
+
+impl<'a, T, A> Drop for UpgradableReadGuard<'a, T, A>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+{
+    fn drop(&mut self) {
+        self.lease.done_upgradable_reading();
+    }
+}
+
+/// Like `DrainGuard`, but owns a clone of the `Arc` instead of borrowing
+/// the lease.
+#[derive(Debug)]
+pub struct OwnedDrainGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    lease: Arc<RWLease<T, A>>,
+    ready: bool,
+}
+
+impl<T, A> OwnedDrainGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn new(lease: Arc<RWLease<T, A>>, ready: bool) -> OwnedDrainGuard<T, A> {
+        OwnedDrainGuard { lease, ready }
+    }
+
+    /// Attempts to upgrade to an OwnedWriteGuard. If readers are still
+    /// locking it, returns self so you can try again
+    pub fn upgrade(self) -> Result<OwnedWriteGuard<T, A>, Self> {
+        if self.ready || self.lease.poll_write_upgrade() {
+            // Move the Arc out rather than cloning it: `self` never runs
+            // its destructor (we forget it below), so this doesn't leave
+            // behind an extra strong count the way clone-then-forget would.
+            let lease = unsafe { core::ptr::read(&self.lease) };
+            core::mem::forget(self);
+            Ok(OwnedWriteGuard::new(lease))
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl<T, A> Drop for OwnedDrainGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn drop(&mut self) {
+        // See `DrainGuard::drop` for why only the writer bit is cleared.
+        self.lease.atomic.fetch_and(!RWLease::<T, A>::writer_bit(), Ordering::SeqCst);
+    }
+}
+
+/// Like `ReadGuard`, but owns a clone of the `Arc` instead of borrowing
+/// the lease, so it can outlive the scope that took it out.
+#[derive(Debug)]
+pub struct OwnedReadGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    lease: Arc<RWLease<T, A>>,
+}
+
+impl<T, A> OwnedReadGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn new(lease: Arc<RWLease<T, A>>) -> OwnedReadGuard<T, A> {
+        OwnedReadGuard { lease }
+    }
+}
+
+impl<T, A> Deref for OwnedReadGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.value.get() }
+    }
+}
+
+impl<T, A> Drop for OwnedReadGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn drop(&mut self) {
+        self.lease.done_reading();
+    }
+}
+
+/// Like `WriteGuard`, but owns a clone of the `Arc` instead of borrowing
+/// the lease, so it can outlive the scope that took it out.
+#[derive(Debug)]
+pub struct OwnedWriteGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    lease: Arc<RWLease<T, A>>,
+}
+
+impl<T, A> OwnedWriteGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn new(lease: Arc<RWLease<T, A>>) -> OwnedWriteGuard<T, A> {
+        OwnedWriteGuard { lease }
+    }
+}
+
+impl<T, A> Deref for OwnedWriteGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lease.value.get() }
+    }
+}
+
+impl<T, A> DerefMut for OwnedWriteGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lease.value.get() }
+    }
+}
+
+impl<T, A> Drop for OwnedWriteGuard<T, A>
+where
+    A: AtomicInt,
+    A::Prim: AddSign,
+{
+    fn drop(&mut self) {
+        self.lease.done_writing();
+    }
+}
+
+/// A read guard projected onto one of `T`'s fields by `ReadGuard::map`
+/// or `try_map`. Releases the read lease on drop, same as the
+/// `ReadGuard` it came from.
+#[derive(Debug)]
+pub struct MappedReadGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    lease: &'a RWLease<T, A>,
+    value: *const U,
+}
+
+impl<'a, T, A, U> Deref for MappedReadGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T, A, U> Drop for MappedReadGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    fn drop(&mut self) {
+        self.lease.done_reading();
+    }
+}
+
+/// A write guard projected onto one of `T`'s fields by `WriteGuard::map`
+/// or `try_map`. Releases the write lease on drop, same as the
+/// `WriteGuard` it came from.
+#[derive(Debug)]
+pub struct MappedWriteGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    lease: &'a RWLease<T, A>,
+    value: *mut U,
+}
+
+impl<'a, T, A, U> Deref for MappedWriteGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    type Target = U;
+    fn deref(&self) -> &U {
+        unsafe { &*self.value }
+    }
+}
+
+impl<'a, T, A, U> DerefMut for MappedWriteGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    fn deref_mut(&mut self) -> &mut U {
+        unsafe { &mut *self.value }
+    }
+}
+
+impl<'a, T, A, U> Drop for MappedWriteGuard<'a, T, A, U>
+where
+    A: 'a + AtomicInt,
+    A::Prim: AddSign,
+    T: 'a,
+    U: 'a,
+{
+    fn drop(&mut self) {
+        self.lease.done_writing();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use core::sync::atomic::AtomicU8;
@@ -298,8 +797,8 @@ mod tests {
 
     #[test]
     fn read_with_writer() {
-        // maximum readers, writer bit
-        let rw: RWLease<u8, AtomicU8> = RWLease::new_with_state(128, 123);
+        // writer bit set, no readers
+        let rw: RWLease<u8, AtomicU8> = RWLease::new_with_state(1, 123);
         assert_eq!(rw.read().unwrap_err(), Blocked::Writer);
     }
 
@@ -312,7 +811,8 @@ mod tests {
 
     #[test]
     fn read_with_max_readers() {
-        let rw: RWLease<u8, AtomicU8> = RWLease::new_with_state(127, 123);
+        // as many readers as will fit without the count wrapping into the writer bit
+        let rw: RWLease<u8, AtomicU8> = RWLease::new_with_state(252, 123);
         assert_eq!(rw.read().unwrap_err(), Blocked::Readers);
     }
 
@@ -330,4 +830,116 @@ mod tests {
         let r = rw.read().expect("read guard");
         assert_eq!(*r, 124);
     }
+
+    #[test]
+    fn solo_upgradable_read() {
+        let rw: RWLease<usize> = RWLease::new(123);
+        let u = rw.upgradable_read().expect("upgradable read guard");
+        assert_eq!(*u, 123);
+        let mut w = u.upgrade().expect("write guard");
+        *w = 124;
+        assert_eq!(*w, 124);
+        drop(w);
+        let r = rw.read().expect("read guard");
+        assert_eq!(*r, 124);
+    }
+
+    #[test]
+    fn upgradable_read_blocked_by_second_upgradable_read() {
+        let rw: RWLease<usize> = RWLease::new(123);
+        let _u = rw.upgradable_read().expect("upgradable read guard");
+        assert_eq!(rw.upgradable_read().unwrap_err(), Blocked::Writer);
+    }
+
+    #[test]
+    fn upgrade_waits_for_other_readers() {
+        let rw: RWLease<usize> = RWLease::new(123);
+        let u = rw.upgradable_read().expect("upgradable read guard");
+        let r = rw.read().expect("read guard");
+        let u = u.upgrade().unwrap_err();
+        drop(r);
+        u.upgrade().expect("write guard");
+    }
+
+    #[test]
+    fn downgrade_then_read() {
+        let rw: RWLease<usize> = RWLease::new(123);
+        let w = rw.write().expect("drain guard").upgrade().expect("write guard");
+        let u = w.downgrade();
+        assert_eq!(*u, 123);
+        assert_eq!(*rw.read().expect("read guard"), 123);
+    }
+
+    #[test]
+    fn solo_owned_reading() {
+        let rw = Arc::new(RWLease::<usize>::new(123));
+        let r = rw.read_owned().expect("owned read guard");
+        assert_eq!(*r, 123);
+    }
+
+    #[test]
+    fn solo_owned_writing() {
+        let rw = Arc::new(RWLease::<usize>::new(123));
+        {
+            let d = rw.write_owned().expect("owned drain guard");
+            let mut w = d.upgrade().expect("owned write guard");
+            *w = 124;
+        }
+        assert_eq!(*rw.read_owned().expect("owned read guard"), 124);
+    }
+
+    #[test]
+    fn owned_drain_guard_upgrade_does_not_leak_arc() {
+        let rw = Arc::new(RWLease::<usize>::new(123));
+        let d = rw.write_owned().expect("owned drain guard");
+        let w = d.upgrade().expect("owned write guard");
+        assert_eq!(Arc::strong_count(&rw), 2);
+        drop(w);
+        assert_eq!(Arc::strong_count(&rw), 1);
+    }
+
+    #[test]
+    fn owned_guard_outlives_original_arc() {
+        let rw = Arc::new(RWLease::<usize>::new(123));
+        let r = rw.read_owned().expect("owned read guard");
+        drop(rw);
+        assert_eq!(*r, 123);
+    }
+
+    #[test]
+    fn mapped_read_guard() {
+        let rw: RWLease<(usize, usize)> = RWLease::new((123, 456));
+        let r = rw.read().expect("read guard").map(|pair| &pair.1);
+        assert_eq!(*r, 456);
+        // A mapped read guard still holds just one reader's share, not
+        // an exclusive lock: a second concurrent read succeeds.
+        let r2 = rw.read().expect("read guard");
+        assert_eq!(*r2, (123, 456));
+        drop(r2);
+        drop(r);
+        rw.read().expect("read guard");
+    }
+
+    #[test]
+    fn mapped_write_guard() {
+        let rw: RWLease<(usize, usize)> = RWLease::new((123, 456));
+        {
+            let mut w = rw
+                .write()
+                .expect("drain guard")
+                .upgrade()
+                .expect("write guard")
+                .map(|pair| &mut pair.1);
+            *w = 789;
+        }
+        assert_eq!(*rw.read().expect("read guard"), (123, 789));
+    }
+
+    #[test]
+    fn try_map_declined_returns_original_guard() {
+        let rw: RWLease<(usize, usize)> = RWLease::new((123, 456));
+        let r = rw.read().expect("read guard");
+        let r = r.try_map(|_pair: &(usize, usize)| None::<&usize>).unwrap_err();
+        assert_eq!(*r, (123, 456));
+    }
 }